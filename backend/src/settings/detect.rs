@@ -0,0 +1,37 @@
+//! Hardware auto-detection: picks the right settings driver for the running device.
+
+use crate::settings::TCpus;
+
+const DMI_BOARD_NAME_PATH: &str = "/sys/class/dmi/id/board_name";
+
+fn is_steam_deck() -> bool {
+    std::fs::read_to_string(DMI_BOARD_NAME_PATH)
+        .map(|board| matches!(board.trim(), "Jupiter" | "Galileo"))
+        .unwrap_or(false)
+}
+
+/// Pick the CPU settings driver for the currently running hardware.
+pub fn auto_detect_provider() -> Box<dyn TCpus> {
+    if is_steam_deck() {
+        Box::new(super::steam_deck_adv::Cpus::system_default())
+    } else if super::generic::is_amd_ryzen() {
+        Box::new(super::generic::Cpus::system_default())
+    } else {
+        Box::new(super::unknown::Cpus::system_default())
+    }
+}
+
+/// Run auto-detection once at startup and log the result.
+pub fn auto_detect0() -> Box<dyn TCpus> {
+    let provider = auto_detect_provider();
+    log::info!("Auto-detected CPU driver: {:?}", provider.provider());
+    provider
+}
+
+pub mod limits_worker {
+    /// Placeholder for a future periodic hardware-limits refresh.
+    ///
+    /// No refresh logic exists yet, so this intentionally does nothing
+    /// rather than spin up a thread that idles forever.
+    pub fn spawn() {}
+}
@@ -0,0 +1,16 @@
+use crate::settings::SettingVariant;
+
+#[derive(Debug, Clone)]
+pub struct SettingError {
+    pub msg: String,
+    pub setting: SettingVariant,
+}
+
+/// Format a batch of setting errors as one message per line, for logging.
+pub fn format_errors(errors: &[SettingError]) -> String {
+    errors
+        .iter()
+        .map(|e| format!("[{:?}] {}", e.setting, e.msg))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,395 @@
+use std::convert::Into;
+
+use crate::api::RangeLimit;
+use crate::settings::steam_deck_adv::cpu::{available_governors, cpu_governor_path, cpu_online_path};
+use crate::settings::MinMax;
+use crate::settings::{OnResume, OnSet, SettingError};
+use crate::settings::{TCpu, TCpus};
+use crate::persist::CpuJson;
+
+const CPU_PRESENT_PATH: &str = "/sys/devices/system/cpu/present";
+const CPU_SMT_PATH: &str = "/sys/devices/system/cpu/smt/control";
+
+#[derive(Debug, Clone)]
+pub struct Cpus {
+    pub cpus: Vec<Cpu>,
+    pub smt: bool,
+    pub smt_capable: bool,
+}
+
+impl OnSet for Cpus {
+    fn on_set(&mut self) -> Result<(), Vec<SettingError>> {
+        let mut errors = Vec::new();
+        if self.smt_capable {
+            let smt_val = if self.smt { "on" } else { "off" };
+            if let Err(e) = usdpl_back::api::files::write_single(CPU_SMT_PATH, smt_val) {
+                errors.push(SettingError {
+                    msg: format!("Failed to write `{}` to `{}`: {}", smt_val, CPU_SMT_PATH, e),
+                    setting: crate::settings::SettingVariant::Cpu,
+                });
+            }
+        }
+        for (i, cpu) in self.cpus.as_mut_slice().iter_mut().enumerate() {
+            cpu.do_set_online = self.smt || i % 2 == 0;
+            if let Err(mut cpu_errors) = cpu.on_set() {
+                errors.append(&mut cpu_errors);
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl OnResume for Cpus {
+    fn on_resume(&self) -> Result<(), SettingError> {
+        for cpu in &self.cpus {
+            cpu.on_resume()?;
+        }
+        Ok(())
+    }
+}
+
+impl Cpus {
+    fn system_smt_capabilities() -> (bool, bool) {
+        crate::settings::steam_deck_adv::Cpus::system_smt_capabilities()
+    }
+
+    pub fn system_default() -> Self {
+        if let Some(max_cpu) = crate::settings::steam_deck_adv::Cpus::cpu_count() {
+            let mut sys_cpus = Vec::with_capacity(max_cpu);
+            for i in 0..max_cpu {
+                sys_cpus.push(Cpu::from_sys(i));
+            }
+            let (smt_status, can_smt) = Self::system_smt_capabilities();
+            Self {
+                cpus: sys_cpus,
+                smt: smt_status,
+                smt_capable: can_smt,
+            }
+        } else {
+            Self {
+                cpus: vec![],
+                smt: false,
+                smt_capable: false,
+            }
+        }
+    }
+
+    #[inline]
+    pub fn from_json(mut other: Vec<CpuJson>, version: u64) -> Self {
+        let (_, can_smt) = Self::system_smt_capabilities();
+        let mut result = Vec::with_capacity(other.len());
+        let max_cpus = crate::settings::steam_deck_adv::Cpus::cpu_count();
+        for (i, cpu) in other.drain(..).enumerate() {
+            // prevent having more CPUs than available
+            if let Some(max_cpus) = max_cpus {
+                if i == max_cpus {
+                    break;
+                }
+            }
+            result.push(Cpu::from_json(cpu, version, i));
+        }
+        if let Some(max_cpus) = max_cpus {
+            if result.len() != max_cpus {
+                let mut sys_cpus = Cpus::system_default();
+                // backfill any CPUs missing from a profile saved on a
+                // machine with fewer cores than this one
+                result.extend(sys_cpus.cpus.drain(result.len()..));
+            }
+        }
+        Self {
+            cpus: result,
+            smt: true,
+            smt_capable: can_smt,
+        }
+    }
+}
+
+impl TCpus for Cpus {
+    fn limits(&self) -> crate::api::CpusLimits {
+        crate::api::CpusLimits {
+            cpus: self.cpus.iter().map(|x| x.limits()).collect(),
+            count: self.cpus.len(),
+            smt_capable: self.smt_capable,
+            core_pairs: None,
+        }
+    }
+
+    fn json(&self) -> Vec<crate::persist::CpuJson> {
+        self.cpus.iter().map(|x| x.to_owned().into()).collect()
+    }
+
+    fn cpus(&mut self) -> Vec<&mut dyn TCpu> {
+        self.cpus.iter_mut().map(|x| x as &mut dyn TCpu).collect()
+    }
+
+    fn len(&self) -> usize {
+        self.cpus.len()
+    }
+
+    fn provider(&self) -> crate::persist::DriverJson {
+        crate::persist::DriverJson::Generic
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Cpu {
+    pub online: bool,
+    pub clock_limits: Option<MinMax<u64>>,
+    pub governor: String,
+    pub skip_resume_reclock: bool,
+    index: usize,
+    /// hardware clock range, read from `cpuinfo_{min,max}_freq`
+    range: MinMax<u64>,
+    do_set_online: bool,
+}
+
+impl Cpu {
+    #[inline]
+    pub fn from_json(other: CpuJson, _version: u64, i: usize) -> Self {
+        Self {
+            online: other.online,
+            clock_limits: other.clock_limits.map(|x| MinMax::from_json(x, _version)),
+            governor: other.governor,
+            skip_resume_reclock: other.skip_resume_reclock,
+            index: i,
+            range: Self::hw_clock_range(i),
+            do_set_online: true,
+        }
+    }
+
+    fn hw_clock_range(cpu_index: usize) -> MinMax<u64> {
+        let min = usdpl_back::api::files::read_single(cpuinfo_min_freq_path(cpu_index)).unwrap_or(400);
+        let max = usdpl_back::api::files::read_single(cpuinfo_max_freq_path(cpu_index)).unwrap_or(4000);
+        MinMax { min, max }
+    }
+
+    fn set_all(&mut self) -> Result<(), Vec<SettingError>> {
+        let mut errors = Vec::new();
+        // set cpu online/offline
+        if self.index != 0 && self.do_set_online {
+            // cpu0 cannot be disabled
+            let online_path = cpu_online_path(self.index);
+            if let Err(e) = usdpl_back::api::files::write_single(&online_path, self.online as u8) {
+                errors.push(SettingError {
+                    msg: format!("Failed to write to `{}`: {}", &online_path, e),
+                    setting: crate::settings::SettingVariant::Cpu,
+                });
+            }
+        }
+        // set clock limits
+        if let Some(clock_limits) = &self.clock_limits {
+            let min_path = cpu_scaling_min_freq_path(self.index);
+            if let Err(e) = usdpl_back::api::files::write_single(&min_path, clock_limits.min) {
+                errors.push(SettingError {
+                    msg: format!(
+                        "Failed to write `{}` to `{}`: {}",
+                        clock_limits.min, &min_path, e
+                    ),
+                    setting: crate::settings::SettingVariant::Cpu,
+                });
+            }
+            let max_path = cpu_scaling_max_freq_path(self.index);
+            if let Err(e) = usdpl_back::api::files::write_single(&max_path, clock_limits.max) {
+                errors.push(SettingError {
+                    msg: format!(
+                        "Failed to write `{}` to `{}`: {}",
+                        clock_limits.max, &max_path, e
+                    ),
+                    setting: crate::settings::SettingVariant::Cpu,
+                });
+            }
+        } else {
+            // no manual limits, restore full hardware range
+            let min_path = cpu_scaling_min_freq_path(self.index);
+            if let Err(e) = usdpl_back::api::files::write_single(&min_path, self.range.min) {
+                errors.push(SettingError {
+                    msg: format!("Failed to write `{}` to `{}`: {}", self.range.min, &min_path, e),
+                    setting: crate::settings::SettingVariant::Cpu,
+                });
+            }
+            let max_path = cpu_scaling_max_freq_path(self.index);
+            if let Err(e) = usdpl_back::api::files::write_single(&max_path, self.range.max) {
+                errors.push(SettingError {
+                    msg: format!("Failed to write `{}` to `{}`: {}", self.range.max, &max_path, e),
+                    setting: crate::settings::SettingVariant::Cpu,
+                });
+            }
+        }
+
+        // set governor
+        if self.index == 0 || self.online {
+            let governor_path = cpu_governor_path(self.index);
+            if let Err(e) = usdpl_back::api::files::write_single(&governor_path, &self.governor) {
+                errors.push(SettingError {
+                    msg: format!(
+                        "Failed to write `{}` to `{}`: {}",
+                        &self.governor, &governor_path, e
+                    ),
+                    setting: crate::settings::SettingVariant::Cpu,
+                });
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Set only online state and governor, leaving clock limits untouched.
+    /// Used on resume when `skip_resume_reclock` is set, so a manual
+    /// scaling_min_freq/scaling_max_freq isn't clobbered by this driver's
+    /// normal resume reclock.
+    fn set_online_and_governor(&self) -> Result<(), Vec<SettingError>> {
+        let mut errors = Vec::new();
+        if self.index != 0 && self.do_set_online {
+            let online_path = cpu_online_path(self.index);
+            if let Err(e) = usdpl_back::api::files::write_single(&online_path, self.online as u8) {
+                errors.push(SettingError {
+                    msg: format!("Failed to write to `{}`: {}", &online_path, e),
+                    setting: crate::settings::SettingVariant::Cpu,
+                });
+            }
+        }
+        if self.index == 0 || self.online {
+            let governor_path = cpu_governor_path(self.index);
+            if let Err(e) = usdpl_back::api::files::write_single(&governor_path, &self.governor) {
+                errors.push(SettingError {
+                    msg: format!(
+                        "Failed to write `{}` to `{}`: {}",
+                        &self.governor, &governor_path, e
+                    ),
+                    setting: crate::settings::SettingVariant::Cpu,
+                });
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn clamp_all(&mut self) {
+        if let Some(clock_limits) = &mut self.clock_limits {
+            clock_limits.min = clock_limits.min.clamp(self.range.min, self.range.max);
+            clock_limits.max = clock_limits.max.clamp(self.range.min, self.range.max);
+        }
+    }
+
+    fn from_sys(cpu_index: usize) -> Self {
+        Self {
+            online: usdpl_back::api::files::read_single(cpu_online_path(cpu_index)).unwrap_or(1u8) != 0,
+            clock_limits: None,
+            governor: usdpl_back::api::files::read_single(cpu_governor_path(cpu_index))
+                .unwrap_or("schedutil".to_owned()),
+            skip_resume_reclock: false,
+            index: cpu_index,
+            range: Self::hw_clock_range(cpu_index),
+            do_set_online: true,
+        }
+    }
+
+    fn limits(&self) -> crate::api::CpuLimits {
+        crate::api::CpuLimits {
+            clock_min_limits: Some(RangeLimit {
+                min: self.range.min,
+                max: self.range.max,
+            }),
+            clock_max_limits: Some(RangeLimit {
+                min: self.range.min,
+                max: self.range.max,
+            }),
+            clock_step: 100,
+            governors: available_governors(self.index),
+        }
+    }
+}
+
+impl Into<CpuJson> for Cpu {
+    #[inline]
+    fn into(self) -> CpuJson {
+        CpuJson {
+            online: self.online,
+            clock_limits: self.clock_limits.map(|x| x.into()),
+            governor: self.governor,
+            skip_resume_reclock: self.skip_resume_reclock,
+        }
+    }
+}
+
+impl OnSet for Cpu {
+    fn on_set(&mut self) -> Result<(), Vec<SettingError>> {
+        self.clamp_all();
+        self.set_all()
+    }
+}
+
+impl OnResume for Cpu {
+    fn on_resume(&self) -> Result<(), SettingError> {
+        let result = if self.skip_resume_reclock {
+            self.set_online_and_governor()
+        } else {
+            let mut copy = self.clone();
+            copy.set_all()
+        };
+        result.map_err(|errors| SettingError {
+            msg: crate::settings::format_errors(&errors),
+            setting: crate::settings::SettingVariant::Cpu,
+        })
+    }
+}
+
+impl TCpu for Cpu {
+    fn online(&mut self) -> &mut bool {
+        &mut self.online
+    }
+
+    fn governor(&mut self, governor: String) {
+        if available_governors(self.index).contains(&governor) {
+            self.governor = governor;
+        } else {
+            log::warn!(
+                "Ignoring attempt to set CPU {} governor to unavailable `{}`",
+                self.index,
+                governor
+            );
+        }
+    }
+
+    fn get_governor(&self) -> &'_ str {
+        &self.governor
+    }
+
+    fn clock_limits(&mut self, limits: Option<MinMax<u64>>) {
+        self.clock_limits = limits;
+    }
+
+    fn get_clock_limits(&self) -> Option<&MinMax<u64>> {
+        self.clock_limits.as_ref()
+    }
+}
+
+#[inline]
+fn cpu_scaling_min_freq_path(index: usize) -> String {
+    format!("/sys/devices/system/cpu/cpu{}/cpufreq/scaling_min_freq", index)
+}
+
+#[inline]
+fn cpu_scaling_max_freq_path(index: usize) -> String {
+    format!("/sys/devices/system/cpu/cpu{}/cpufreq/scaling_max_freq", index)
+}
+
+#[inline]
+fn cpuinfo_min_freq_path(index: usize) -> String {
+    format!("/sys/devices/system/cpu/cpu{}/cpufreq/cpuinfo_min_freq", index)
+}
+
+#[inline]
+fn cpuinfo_max_freq_path(index: usize) -> String {
+    format!("/sys/devices/system/cpu/cpu{}/cpufreq/cpuinfo_max_freq", index)
+}
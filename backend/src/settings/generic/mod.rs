@@ -0,0 +1,17 @@
+pub(crate) mod cpu;
+
+pub use cpu::{Cpu, Cpus};
+
+const CPUINFO_PATH: &str = "/proc/cpuinfo";
+
+/// Whether this looks like a generic (non-Steam Deck) AMD Ryzen system,
+/// as opposed to hardware with its own dedicated driver.
+pub fn is_amd_ryzen() -> bool {
+    std::fs::read_to_string(CPUINFO_PATH)
+        .map(|contents| {
+            contents
+                .lines()
+                .any(|line| line.starts_with("model name") && line.contains("AMD Ryzen"))
+        })
+        .unwrap_or(false)
+}
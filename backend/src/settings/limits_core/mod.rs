@@ -0,0 +1,144 @@
+//! Hardware-dependent setting limits, selected at startup by matching the
+//! running device against a list of `Config`s (à la the dev-driver
+//! `limits_core` format), instead of being baked into the binary.
+
+use serde::Deserialize;
+
+use crate::settings::MinMax;
+
+const LIMITS_CONFIG_PATH: &str = "/usr/share/powertools/limits_core/cpu.json";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Conditions {
+    #[serde(default)]
+    pub dmi: Option<String>,
+    #[serde(default)]
+    pub cpuinfo: Option<String>,
+    #[serde(default)]
+    pub os: Option<String>,
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub file_exists: Option<String>,
+}
+
+impl Conditions {
+    /// Evaluate this condition set against the running system.
+    pub fn matches(&self) -> bool {
+        if let Some(dmi) = &self.dmi {
+            if !Self::dmi_matches(dmi) {
+                return false;
+            }
+        }
+        if let Some(needle) = &self.cpuinfo {
+            if !Self::file_contains("/proc/cpuinfo", needle) {
+                return false;
+            }
+        }
+        if let Some(os) = &self.os {
+            if !Self::file_contains("/etc/os-release", os) {
+                return false;
+            }
+        }
+        if let Some(path) = &self.file_exists {
+            if !std::path::Path::new(path).exists() {
+                return false;
+            }
+        }
+        if let Some(command) = &self.command {
+            match std::process::Command::new("sh").arg("-c").arg(command).status() {
+                Ok(status) => {
+                    if !status.success() {
+                        return false;
+                    }
+                }
+                Err(_) => return false,
+            }
+        }
+        true
+    }
+
+    fn dmi_matches(needle: &str) -> bool {
+        const DMI_FIELDS: &[&str] = &["sys_vendor", "product_name", "board_name"];
+        DMI_FIELDS.iter().any(|field| {
+            Self::file_contains(&format!("/sys/class/dmi/id/{}", field), needle)
+        })
+    }
+
+    fn file_contains(path: &str, needle: &str) -> bool {
+        std::fs::read_to_string(path)
+            .map(|contents| contents.contains(needle))
+            .unwrap_or(false)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CpuLimits {
+    /// Allowed range for the minimum clock slider.
+    pub clock_min: MinMax<u64>,
+    /// Allowed range for the maximum clock slider.
+    pub clock_max: MinMax<u64>,
+    pub clock_step: u64,
+}
+
+impl CpuLimits {
+    /// Whether `clock_min`/`clock_max` are sane (`min <= max`) and therefore
+    /// safe to use as `u64::clamp` bounds, which panic otherwise.
+    fn is_valid(&self) -> bool {
+        self.clock_min.min <= self.clock_min.max && self.clock_max.min <= self.clock_max.max
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Limits {
+    pub cpu: CpuLimits,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub conditions: Conditions,
+    pub limits: Limits,
+}
+
+impl Limits {
+    /// Steam Deck's known-good ranges, used when no usable config matches (or none is installed).
+    pub fn steam_deck_default() -> Self {
+        Self {
+            cpu: CpuLimits {
+                clock_min: MinMax { min: 1400, max: 3500 },
+                clock_max: MinMax { min: 500, max: 3500 },
+                clock_step: 100,
+            },
+        }
+    }
+
+    /// Load `/usr/share/powertools/limits_core/cpu.json`, if present, and
+    /// return the `Limits` of the first `Config` whose `conditions` match
+    /// this system and whose clock ranges are valid (a matching entry with
+    /// `min > max` is skipped); falls back to [`Limits::steam_deck_default`].
+    pub fn system_default() -> Self {
+        Self::detect(LIMITS_CONFIG_PATH).unwrap_or_else(Self::steam_deck_default)
+    }
+
+    fn detect(config_path: &str) -> Option<Self> {
+        let data = std::fs::read_to_string(config_path).ok()?;
+        let configs: Vec<Config> = serde_json::from_str(&data)
+            .map_err(|e| log::warn!("Failed to parse `{}`: {}", config_path, e))
+            .ok()?;
+        configs
+            .into_iter()
+            .filter(|config| config.conditions.matches())
+            .find_map(|config| {
+                if config.limits.cpu.is_valid() {
+                    Some(config.limits)
+                } else {
+                    log::warn!(
+                        "Ignoring a matching entry in `{}` with invalid clock ranges (min > max)",
+                        config_path
+                    );
+                    None
+                }
+            })
+    }
+}
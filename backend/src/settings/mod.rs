@@ -2,11 +2,13 @@ mod detect;
 pub mod driver;
 mod error;
 mod general;
+pub mod limits_core;
 mod min_max;
 mod traits;
 
 pub mod generic;
 pub mod steam_deck;
+pub mod steam_deck_adv;
 pub mod unknown;
 
 pub use detect::{auto_detect0, auto_detect_provider, limits_worker::spawn as limits_worker_spawn};
@@ -14,7 +16,7 @@ pub use driver::Driver;
 pub use general::{SettingVariant, Settings, General};
 pub use min_max::MinMax;
 
-pub use error::SettingError;
+pub use error::{format_errors, SettingError};
 pub use traits::{OnResume, OnSet, SettingsRange, TGeneral, TGpu, TCpus, TBattery, TCpu};
 
 #[cfg(test)]
@@ -0,0 +1,213 @@
+use crate::settings::steam_deck_adv::cpu::{available_governors, cpu_governor_path, cpu_online_path};
+use crate::settings::MinMax;
+use crate::settings::{OnResume, OnSet, SettingError};
+use crate::settings::{TCpu, TCpus};
+use crate::persist::CpuJson;
+
+/// Base (pre-"advance") Steam Deck CPU driver: online/governor control only,
+/// no `pp_od_clk_voltage` overclocking.
+#[derive(Debug, Clone)]
+pub struct Cpus {
+    pub cpus: Vec<Cpu>,
+    pub smt: bool,
+    pub smt_capable: bool,
+}
+
+impl OnSet for Cpus {
+    fn on_set(&mut self) -> Result<(), Vec<SettingError>> {
+        let mut errors = Vec::new();
+        for cpu in self.cpus.iter_mut() {
+            if let Err(mut cpu_errors) = cpu.on_set() {
+                errors.append(&mut cpu_errors);
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl OnResume for Cpus {
+    fn on_resume(&self) -> Result<(), SettingError> {
+        for cpu in &self.cpus {
+            cpu.on_resume()?;
+        }
+        Ok(())
+    }
+}
+
+impl Cpus {
+    pub fn system_default() -> Self {
+        let (smt_status, can_smt) = crate::settings::steam_deck_adv::Cpus::system_smt_capabilities();
+        let count = crate::settings::steam_deck_adv::Cpus::cpu_count().unwrap_or(0);
+        Self {
+            cpus: (0..count).map(Cpu::from_sys).collect(),
+            smt: smt_status,
+            smt_capable: can_smt,
+        }
+    }
+
+    #[inline]
+    pub fn from_json(other: Vec<CpuJson>, version: u64) -> Self {
+        let (_, can_smt) = crate::settings::steam_deck_adv::Cpus::system_smt_capabilities();
+        Self {
+            cpus: other
+                .into_iter()
+                .enumerate()
+                .map(|(i, cpu)| Cpu::from_json(cpu, version, i))
+                .collect(),
+            smt: true,
+            smt_capable: can_smt,
+        }
+    }
+}
+
+impl TCpus for Cpus {
+    fn limits(&self) -> crate::api::CpusLimits {
+        crate::api::CpusLimits {
+            cpus: self.cpus.iter().map(|x| x.limits()).collect(),
+            count: self.cpus.len(),
+            smt_capable: self.smt_capable,
+            core_pairs: None,
+        }
+    }
+
+    fn json(&self) -> Vec<crate::persist::CpuJson> {
+        self.cpus.iter().map(|x| x.to_owned().into()).collect()
+    }
+
+    fn cpus(&mut self) -> Vec<&mut dyn TCpu> {
+        self.cpus.iter_mut().map(|x| x as &mut dyn TCpu).collect()
+    }
+
+    fn len(&self) -> usize {
+        self.cpus.len()
+    }
+
+    fn provider(&self) -> crate::persist::DriverJson {
+        crate::persist::DriverJson::SteamDeck
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Cpu {
+    pub online: bool,
+    pub governor: String,
+    index: usize,
+}
+
+impl Cpu {
+    fn from_sys(index: usize) -> Self {
+        Self {
+            online: usdpl_back::api::files::read_single(cpu_online_path(index)).unwrap_or(1u8) != 0,
+            governor: usdpl_back::api::files::read_single(cpu_governor_path(index))
+                .unwrap_or("schedutil".to_owned()),
+            index,
+        }
+    }
+
+    #[inline]
+    fn from_json(other: CpuJson, _version: u64, index: usize) -> Self {
+        Self {
+            online: other.online,
+            governor: other.governor,
+            index,
+        }
+    }
+
+    fn limits(&self) -> crate::api::CpuLimits {
+        crate::api::CpuLimits {
+            clock_min_limits: None,
+            clock_max_limits: None,
+            clock_step: 0,
+            governors: available_governors(self.index),
+        }
+    }
+}
+
+impl Into<CpuJson> for Cpu {
+    #[inline]
+    fn into(self) -> CpuJson {
+        CpuJson {
+            online: self.online,
+            clock_limits: None,
+            governor: self.governor,
+            skip_resume_reclock: false,
+        }
+    }
+}
+
+impl OnSet for Cpu {
+    fn on_set(&mut self) -> Result<(), Vec<SettingError>> {
+        let mut errors = Vec::new();
+        if self.index != 0 {
+            // cpu0 cannot be disabled
+            let online_path = cpu_online_path(self.index);
+            if let Err(e) = usdpl_back::api::files::write_single(&online_path, self.online as u8) {
+                errors.push(SettingError {
+                    msg: format!("Failed to write to `{}`: {}", &online_path, e),
+                    setting: crate::settings::SettingVariant::Cpu,
+                });
+            }
+        }
+        if self.index == 0 || self.online {
+            let governor_path = cpu_governor_path(self.index);
+            if let Err(e) = usdpl_back::api::files::write_single(&governor_path, &self.governor) {
+                errors.push(SettingError {
+                    msg: format!(
+                        "Failed to write `{}` to `{}`: {}",
+                        &self.governor, &governor_path, e
+                    ),
+                    setting: crate::settings::SettingVariant::Cpu,
+                });
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl OnResume for Cpu {
+    fn on_resume(&self) -> Result<(), SettingError> {
+        let mut copy = self.clone();
+        copy.on_set().map_err(|errors| SettingError {
+            msg: crate::settings::format_errors(&errors),
+            setting: crate::settings::SettingVariant::Cpu,
+        })
+    }
+}
+
+impl TCpu for Cpu {
+    fn online(&mut self) -> &mut bool {
+        &mut self.online
+    }
+
+    fn governor(&mut self, governor: String) {
+        if available_governors(self.index).contains(&governor) {
+            self.governor = governor;
+        } else {
+            log::warn!(
+                "Ignoring attempt to set CPU {} governor to unavailable `{}`",
+                self.index,
+                governor
+            );
+        }
+    }
+
+    fn get_governor(&self) -> &'_ str {
+        &self.governor
+    }
+
+    fn clock_limits(&mut self, _limits: Option<MinMax<u64>>) {
+        // base Steam Deck driver has no clock-limit control
+    }
+
+    fn get_clock_limits(&self) -> Option<&MinMax<u64>> {
+        None
+    }
+}
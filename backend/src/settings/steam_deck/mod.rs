@@ -0,0 +1,3 @@
+pub(crate) mod cpu;
+
+pub use cpu::{Cpu, Cpus};
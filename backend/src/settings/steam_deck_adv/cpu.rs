@@ -1,8 +1,9 @@
 use std::convert::Into;
 
 use crate::api::RangeLimit;
+use crate::settings::limits_core;
 use crate::settings::MinMax;
-use crate::settings::{OnResume, OnSet, SettingError, SettingsRange};
+use crate::settings::{OnResume, OnSet, SettingError};
 use crate::settings::{TCpus, TCpu};
 use crate::persist::CpuJson;
 
@@ -14,39 +15,45 @@ pub struct Cpus {
     pub cpus: Vec<Cpu>,
     pub smt: bool,
     pub smt_capable: bool,
+    pub limits: limits_core::CpuLimits,
+    /// When true, each pair of logical cores sharing a `pp_od_clk_voltage`
+    /// pstate register is coalesced into one authoritative `MinMax` before
+    /// being applied, instead of the frontend's two sliders racing to write
+    /// the same hardware register.
+    pub clock_by_physical_core: bool,
 }
 
 impl OnSet for Cpus {
-    fn on_set(&mut self) -> Result<(), SettingError> {
+    fn on_set(&mut self) -> Result<(), Vec<SettingError>> {
+        let mut errors = Vec::new();
         if self.smt_capable {
             // toggle SMT
-            if self.smt {
-                usdpl_back::api::files::write_single(CPU_SMT_PATH, "on").map_err(|e| {
-                    SettingError {
-                        msg: format!(
-                            "Failed to write `on` to `{}`: {}",
-                            CPU_SMT_PATH, e
-                        ),
-                        setting: crate::settings::SettingVariant::Cpu,
-                    }
-                })?;
-            } else {
-                usdpl_back::api::files::write_single(CPU_SMT_PATH, "off").map_err(|e| {
-                    SettingError {
-                        msg: format!(
-                            "Failed to write `off` to `{}`: {}",
-                            CPU_SMT_PATH, e
-                        ),
-                        setting: crate::settings::SettingVariant::Cpu,
-                    }
-                })?;
+            let smt_val = if self.smt { "on" } else { "off" };
+            if let Err(e) = usdpl_back::api::files::write_single(CPU_SMT_PATH, smt_val) {
+                errors.push(SettingError {
+                    msg: format!("Failed to write `{}` to `{}`: {}", smt_val, CPU_SMT_PATH, e),
+                    setting: crate::settings::SettingVariant::Cpu,
+                });
+            }
+        }
+        if self.clock_by_physical_core {
+            self.coalesce_physical_core_pairs();
+        } else {
+            for cpu in self.cpus.iter_mut() {
+                cpu.coalesced_clock_limits = None;
             }
         }
         for (i, cpu) in self.cpus.as_mut_slice().iter_mut().enumerate() {
             cpu.state.do_set_online = self.smt || i % 2 == 0;
-            cpu.on_set()?;
+            if let Err(mut cpu_errors) = cpu.on_set() {
+                errors.append(&mut cpu_errors);
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
         }
-        Ok(())
     }
 }
 
@@ -73,7 +80,7 @@ impl Cpus {
         None
     }
 
-    fn system_smt_capabilities() -> (bool, bool) {
+    pub(crate) fn system_smt_capabilities() -> (bool, bool) {
         match usdpl_back::api::files::read_single::<_, String, _>(CPU_SMT_PATH) {
             Ok(val) => (val.trim().to_lowercase() == "on", true),
             Err(_) => (false, false)
@@ -81,22 +88,27 @@ impl Cpus {
     }
 
     pub fn system_default() -> Self {
+        let limits = limits_core::Limits::system_default().cpu;
         if let Some(max_cpu) = Self::cpu_count() {
             let mut sys_cpus = Vec::with_capacity(max_cpu);
             for i in 0..max_cpu {
-                sys_cpus.push(Cpu::from_sys(i));
+                sys_cpus.push(Cpu::from_sys(i, limits.clone()));
             }
             let (smt_status, can_smt) = Self::system_smt_capabilities();
             Self {
                 cpus: sys_cpus,
                 smt: smt_status,
                 smt_capable: can_smt,
+                limits,
+                clock_by_physical_core: false,
             }
         } else {
             Self {
                 cpus: vec![],
                 smt: false,
                 smt_capable: false,
+                limits,
+                clock_by_physical_core: false,
             }
         }
     }
@@ -104,6 +116,7 @@ impl Cpus {
     #[inline]
     pub fn from_json(mut other: Vec<CpuJson>, version: u64) -> Self {
         let (_, can_smt) = Self::system_smt_capabilities();
+        let limits = limits_core::Limits::system_default().cpu;
         let mut result = Vec::with_capacity(other.len());
         let max_cpus = Self::cpu_count();
         for (i, cpu) in other.drain(..).enumerate() {
@@ -113,7 +126,7 @@ impl Cpus {
                     break;
                 }
             }
-            result.push(Cpu::from_json(cpu, version, i));
+            result.push(Cpu::from_json(cpu, version, i, limits.clone()));
         }
         if let Some(max_cpus) = max_cpus {
             if result.len() != max_cpus {
@@ -127,6 +140,35 @@ impl Cpus {
             cpus: result,
             smt: true,
             smt_capable: can_smt,
+            limits,
+            clock_by_physical_core: false,
+        }
+    }
+
+    /// Coalesce each physical core's pair of logical cores into one
+    /// authoritative `MinMax` (min of mins, max of maxes) so both halves of
+    /// the shared `pp_od_clk_voltage` pstate register agree. The result is
+    /// stored in `coalesced_clock_limits`, a transient field used only for
+    /// the hardware write -- `clock_limits` itself is left alone so the
+    /// per-core value the user set is still there if physical-core mode is
+    /// turned back off.
+    fn coalesce_physical_core_pairs(&mut self) {
+        for pair in self.cpus.chunks_mut(2) {
+            if let [a, b] = pair {
+                let coalesced = match (&a.clock_limits, &b.clock_limits) {
+                    (Some(x), Some(y)) => Some(MinMax {
+                        min: x.min.min(y.min),
+                        max: x.max.max(y.max),
+                    }),
+                    (Some(x), None) => Some(x.clone()),
+                    (None, Some(y)) => Some(y.clone()),
+                    (None, None) => None,
+                };
+                a.coalesced_clock_limits = coalesced.clone();
+                b.coalesced_clock_limits = coalesced;
+            } else if let [a] = pair {
+                a.coalesced_clock_limits = a.clock_limits.clone();
+            }
         }
     }
 }
@@ -137,6 +179,16 @@ impl TCpus for Cpus {
             cpus: self.cpus.iter().map(|x| x.limits()).collect(),
             count: self.cpus.len(),
             smt_capable: self.smt_capable,
+            core_pairs: if self.clock_by_physical_core {
+                Some(
+                    self.cpus
+                        .chunks(2)
+                        .map(|pair| (pair[0].index, pair.get(1).map(|c| c.index).unwrap_or(pair[0].index)))
+                        .collect(),
+                )
+            } else {
+                None
+            },
         }
     }
 
@@ -155,6 +207,10 @@ impl TCpus for Cpus {
     fn provider(&self) -> crate::persist::DriverJson {
         crate::persist::DriverJson::SteamDeckAdvance
     }
+
+    fn set_clock_by_physical_core(&mut self, value: bool) {
+        self.clock_by_physical_core = value;
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -162,8 +218,19 @@ pub struct Cpu {
     pub online: bool,
     pub clock_limits: Option<MinMax<u64>>,
     pub governor: String,
+    /// Skip re-asserting clock limits on resume; only online state and
+    /// governor are restored. Works around kernels where reclocking on
+    /// resume is unstable.
+    pub skip_resume_reclock: bool,
     index: usize,
     state: crate::state::steam_deck::Cpu,
+    limits: limits_core::CpuLimits,
+    /// Physical-core-coalesced clock limits to apply for this `on_set`,
+    /// overriding `clock_limits` for the hardware write only. Populated by
+    /// `Cpus::coalesce_physical_core_pairs` and never persisted -- the
+    /// per-core `clock_limits` the user actually set is left untouched so
+    /// switching back to per-logical-core mode doesn't lose it.
+    coalesced_clock_limits: Option<MinMax<u64>>,
 }
 
 const CPU_CLOCK_LIMITS_PATH: &str = "/sys/class/drm/card0/device/pp_od_clk_voltage";
@@ -171,166 +238,205 @@ const CPU_FORCE_LIMITS_PATH: &str = "/sys/class/drm/card0/device/power_dpm_force
 
 impl Cpu {
     #[inline]
-    pub fn from_json(other: CpuJson, version: u64, i: usize) -> Self {
+    pub fn from_json(other: CpuJson, version: u64, i: usize, limits: limits_core::CpuLimits) -> Self {
         match version {
             0 => Self {
                 online: other.online,
                 clock_limits: other.clock_limits.map(|x| MinMax::from_json(x, version)),
                 governor: other.governor,
+                skip_resume_reclock: false,
                 index: i,
                 state: crate::state::steam_deck::Cpu::default(),
+                limits,
+                coalesced_clock_limits: None,
             },
             _ => Self {
                 online: other.online,
                 clock_limits: other.clock_limits.map(|x| MinMax::from_json(x, version)),
                 governor: other.governor,
+                skip_resume_reclock: other.skip_resume_reclock,
                 index: i,
                 state: crate::state::steam_deck::Cpu::default(),
+                limits,
+                coalesced_clock_limits: None,
             },
         }
     }
 
-    fn set_all(&mut self) -> Result<(), SettingError> {
-        // set cpu online/offline
+    /// Restore online state and governor only, without touching clock limits.
+    fn set_online_and_governor(&self) -> Result<(), Vec<SettingError>> {
+        let mut errors = Vec::new();
+        self.push_online_error(&mut errors);
+        self.push_governor_error(&mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn push_online_error(&self, errors: &mut Vec<SettingError>) {
         if self.index != 0 && self.state.do_set_online { // cpu0 cannot be disabled
             let online_path = cpu_online_path(self.index);
-            usdpl_back::api::files::write_single(&online_path, self.online as u8).map_err(|e| {
-                SettingError {
+            if let Err(e) = usdpl_back::api::files::write_single(&online_path, self.online as u8) {
+                errors.push(SettingError {
                     msg: format!("Failed to write to `{}`: {}", &online_path, e),
                     setting: crate::settings::SettingVariant::Cpu,
-                }
-            })?;
+                });
+            }
         }
-        // set clock limits
-        log::debug!("Setting {} to manual", CPU_FORCE_LIMITS_PATH);
-        let mode: String = usdpl_back::api::files::read_single(CPU_FORCE_LIMITS_PATH.to_owned()).unwrap();
-        if mode != "manual" {
-            // set manual control
-            usdpl_back::api::files::write_single(CPU_FORCE_LIMITS_PATH, "manual").map_err(|e| {
-                SettingError {
+    }
+
+    fn push_governor_error(&self, errors: &mut Vec<SettingError>) {
+        if self.index == 0 || self.online {
+            let governor_path = cpu_governor_path(self.index);
+            if let Err(e) = usdpl_back::api::files::write_single(&governor_path, &self.governor) {
+                errors.push(SettingError {
                     msg: format!(
-                        "Failed to write `manual` to `{}`: {}",
-                        CPU_FORCE_LIMITS_PATH, e
+                        "Failed to write `{}` to `{}`: {}",
+                        &self.governor, &governor_path, e
                     ),
                     setting: crate::settings::SettingVariant::Cpu,
+                });
+            }
+        }
+    }
+
+    fn set_all(&mut self) -> Result<(), Vec<SettingError>> {
+        let mut errors = Vec::new();
+        // set cpu online/offline
+        self.push_online_error(&mut errors);
+        // set clock limits
+        log::debug!("Setting {} to manual", CPU_FORCE_LIMITS_PATH);
+        match usdpl_back::api::files::read_single::<_, String, _>(CPU_FORCE_LIMITS_PATH.to_owned()) {
+            Ok(mode) => {
+                if mode != "manual" {
+                    // set manual control
+                    if let Err(e) = usdpl_back::api::files::write_single(CPU_FORCE_LIMITS_PATH, "manual") {
+                        errors.push(SettingError {
+                            msg: format!(
+                                "Failed to write `manual` to `{}`: {}",
+                                CPU_FORCE_LIMITS_PATH, e
+                            ),
+                            setting: crate::settings::SettingVariant::Cpu,
+                        });
+                    }
                 }
-            })?;
+            }
+            Err(e) => {
+                errors.push(SettingError {
+                    msg: format!("Failed to read `{}`: {}", CPU_FORCE_LIMITS_PATH, e),
+                    setting: crate::settings::SettingVariant::Cpu,
+                });
+            }
         }
-        if let Some(clock_limits) = &self.clock_limits {
+        let effective_clock_limits = self.coalesced_clock_limits.clone().or_else(|| self.clock_limits.clone());
+        if let Some(clock_limits) = &effective_clock_limits {
             log::debug!("Setting CPU {} (min, max) clockspeed to ({}, {})", self.index, clock_limits.min, clock_limits.max);
             self.state.clock_limits_set = true;
             // max clock
             let payload_max = format!("p {} 1 {}\n", self.index / 2, clock_limits.max);
-            usdpl_back::api::files::write_single(CPU_CLOCK_LIMITS_PATH, &payload_max).map_err(
-                |e| SettingError {
+            if let Err(e) = usdpl_back::api::files::write_single(CPU_CLOCK_LIMITS_PATH, &payload_max) {
+                errors.push(SettingError {
                     msg: format!(
                         "Failed to write `{}` to `{}`: {}",
                         &payload_max, CPU_CLOCK_LIMITS_PATH, e
                     ),
                     setting: crate::settings::SettingVariant::Cpu,
-                },
-            )?;
+                });
+            }
             // min clock
             let payload_min = format!("p {} 0 {}\n", self.index / 2, clock_limits.min);
-            usdpl_back::api::files::write_single(CPU_CLOCK_LIMITS_PATH, &payload_min).map_err(
-                |e| SettingError {
+            if let Err(e) = usdpl_back::api::files::write_single(CPU_CLOCK_LIMITS_PATH, &payload_min) {
+                errors.push(SettingError {
                     msg: format!(
                         "Failed to write `{}` to `{}`: {}",
                         &payload_min, CPU_CLOCK_LIMITS_PATH, e
                     ),
                     setting: crate::settings::SettingVariant::Cpu,
-                },
-            )?;
+                });
+            }
         } else if self.state.clock_limits_set || self.state.is_resuming {
             self.state.clock_limits_set = false;
             // disable manual clock limits
             log::debug!("Setting CPU {} to default clockspeed", self.index);
             // max clock
-            let payload_max = format!("p {} 1 {}\n", self.index / 2, Self::max().clock_limits.unwrap().max);
-            usdpl_back::api::files::write_single(CPU_CLOCK_LIMITS_PATH, &payload_max).map_err(
-                |e| SettingError {
+            let payload_max = format!("p {} 1 {}\n", self.index / 2, self.limits.clock_max.max);
+            if let Err(e) = usdpl_back::api::files::write_single(CPU_CLOCK_LIMITS_PATH, &payload_max) {
+                errors.push(SettingError {
                     msg: format!(
                         "Failed to write `{}` to `{}`: {}",
                         &payload_max, CPU_CLOCK_LIMITS_PATH, e
                     ),
                     setting: crate::settings::SettingVariant::Cpu,
-                },
-            )?;
+                });
+            }
             // min clock
-            let payload_min = format!("p {} 0 {}\n", self.index / 2, Self::min().clock_limits.unwrap().min);
-            usdpl_back::api::files::write_single(CPU_CLOCK_LIMITS_PATH, &payload_min).map_err(
-                |e| SettingError {
+            let payload_min = format!("p {} 0 {}\n", self.index / 2, self.limits.clock_min.min);
+            if let Err(e) = usdpl_back::api::files::write_single(CPU_CLOCK_LIMITS_PATH, &payload_min) {
+                errors.push(SettingError {
                     msg: format!(
                         "Failed to write `{}` to `{}`: {}",
                         &payload_min, CPU_CLOCK_LIMITS_PATH, e
                     ),
                     setting: crate::settings::SettingVariant::Cpu,
-                },
-            )?;
+                });
+            }
         }
         // commit changes
-        usdpl_back::api::files::write_single(CPU_CLOCK_LIMITS_PATH, "c\n").map_err(|e| {
-            SettingError {
+        if let Err(e) = usdpl_back::api::files::write_single(CPU_CLOCK_LIMITS_PATH, "c\n") {
+            errors.push(SettingError {
                 msg: format!("Failed to write `c` to `{}`: {}", CPU_CLOCK_LIMITS_PATH, e),
                 setting: crate::settings::SettingVariant::Cpu,
-            }
-        })?;
+            });
+        }
 
         // set governor
-        if self.index == 0 || self.online {
-            let governor_path = cpu_governor_path(self.index);
-            usdpl_back::api::files::write_single(&governor_path, &self.governor).map_err(|e| {
-                SettingError {
-                    msg: format!(
-                        "Failed to write `{}` to `{}`: {}",
-                        &self.governor, &governor_path, e
-                    ),
-                    setting: crate::settings::SettingVariant::Cpu,
-                }
-            })?;
+        self.push_governor_error(&mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
         }
-        Ok(())
     }
 
     fn clamp_all(&mut self) {
-        let min = Self::min();
-        let max = Self::max();
         if let Some(clock_limits) = &mut self.clock_limits {
-            let max_boost = max.clock_limits.as_ref().unwrap();
-            let min_boost = min.clock_limits.as_ref().unwrap();
-            clock_limits.min = clock_limits.min.clamp(min_boost.min, max_boost.min);
-            clock_limits.max = clock_limits.max.clamp(min_boost.max, max_boost.max);
+            clock_limits.min = clock_limits.min.clamp(self.limits.clock_min.min, self.limits.clock_min.max);
+            clock_limits.max = clock_limits.max.clamp(self.limits.clock_max.min, self.limits.clock_max.max);
+        }
+        if let Some(clock_limits) = &mut self.coalesced_clock_limits {
+            clock_limits.min = clock_limits.min.clamp(self.limits.clock_min.min, self.limits.clock_min.max);
+            clock_limits.max = clock_limits.max.clamp(self.limits.clock_max.min, self.limits.clock_max.max);
         }
     }
 
-    fn from_sys(cpu_index: usize) -> Self {
+    fn from_sys(cpu_index: usize, limits: limits_core::CpuLimits) -> Self {
         Self {
             online: usdpl_back::api::files::read_single(cpu_online_path(cpu_index)).unwrap_or(1u8) != 0,
             clock_limits: None,
             governor: usdpl_back::api::files::read_single(cpu_governor_path(cpu_index))
                 .unwrap_or("schedutil".to_owned()),
+            skip_resume_reclock: false,
             index: cpu_index,
             state: crate::state::steam_deck::Cpu::default(),
+            limits,
+            coalesced_clock_limits: None,
         }
     }
 
     fn limits(&self) -> crate::api::CpuLimits {
-        let max = Self::max();
-        let max_clocks = max.clock_limits.unwrap();
-
-        let min = Self::min();
-        let min_clocks = min.clock_limits.unwrap();
         crate::api::CpuLimits {
             clock_min_limits: Some(RangeLimit {
-                min: min_clocks.min,
-                max: max_clocks.min
+                min: self.limits.clock_min.min,
+                max: self.limits.clock_min.max,
             }),
             clock_max_limits: Some(RangeLimit {
-                min: min_clocks.max,
-                max: max_clocks.max
+                min: self.limits.clock_max.min,
+                max: self.limits.clock_max.max,
             }),
-            clock_step: 100,
-            governors: vec![], // TODO
+            clock_step: self.limits.clock_step,
+            governors: available_governors(self.index),
         }
     }
 }
@@ -342,12 +448,13 @@ impl Into<CpuJson> for Cpu {
             online: self.online,
             clock_limits: self.clock_limits.map(|x| x.into()),
             governor: self.governor,
+            skip_resume_reclock: self.skip_resume_reclock,
         }
     }
 }
 
 impl OnSet for Cpu {
-    fn on_set(&mut self) -> Result<(), SettingError> {
+    fn on_set(&mut self) -> Result<(), Vec<SettingError>> {
         self.clamp_all();
         self.set_all()
     }
@@ -355,9 +462,17 @@ impl OnSet for Cpu {
 
 impl OnResume for Cpu {
     fn on_resume(&self) -> Result<(), SettingError> {
-        let mut copy = self.clone();
-        copy.state.is_resuming = true;
-        copy.set_all()
+        let result = if self.skip_resume_reclock {
+            self.set_online_and_governor()
+        } else {
+            let mut copy = self.clone();
+            copy.state.is_resuming = true;
+            copy.set_all()
+        };
+        result.map_err(|errors| SettingError {
+            msg: crate::settings::format_errors(&errors),
+            setting: crate::settings::SettingVariant::Cpu,
+        })
     }
 }
 
@@ -367,7 +482,15 @@ impl TCpu for Cpu {
     }
 
     fn governor(&mut self, governor: String) {
-        self.governor = governor;
+        if available_governors(self.index).contains(&governor) {
+            self.governor = governor;
+        } else {
+            log::warn!(
+                "Ignoring attempt to set CPU {} governor to unavailable `{}`",
+                self.index,
+                governor
+            );
+        }
     }
 
     fn get_governor(&self) -> &'_ str {
@@ -383,42 +506,30 @@ impl TCpu for Cpu {
     }
 }
 
-impl SettingsRange for Cpu {
-    #[inline]
-    fn max() -> Self {
-        Self {
-            online: true,
-            clock_limits: Some(MinMax {
-                max: 3500,
-                min: 3500,
-            }),
-            governor: "schedutil".to_owned(),
-            index: usize::MAX,
-            state: crate::state::steam_deck::Cpu::default(),
-        }
-    }
-
-    #[inline]
-    fn min() -> Self {
-        Self {
-            online: false,
-            clock_limits: Some(MinMax { max: 500, min: 1400 }),
-            governor: "schedutil".to_owned(),
-            index: usize::MIN,
-            state: crate::state::steam_deck::Cpu::default(),
-        }
-    }
-}
-
 #[inline]
-fn cpu_online_path(index: usize) -> String {
+pub(crate) fn cpu_online_path(index: usize) -> String {
     format!("/sys/devices/system/cpu/cpu{}/online", index)
 }
 
 #[inline]
-fn cpu_governor_path(index: usize) -> String {
+pub(crate) fn cpu_governor_path(index: usize) -> String {
     format!(
         "/sys/devices/system/cpu/cpu{}/cpufreq/scaling_governor",
         index
     )
 }
+
+#[inline]
+pub(crate) fn cpu_available_governors_path(index: usize) -> String {
+    format!(
+        "/sys/devices/system/cpu/cpu{}/cpufreq/scaling_available_governors",
+        index
+    )
+}
+
+#[inline]
+pub(crate) fn available_governors(cpu_index: usize) -> Vec<String> {
+    usdpl_back::api::files::read_single::<_, String, _>(cpu_available_governors_path(cpu_index))
+        .map(|data| data.split_whitespace().map(|x| x.to_owned()).collect())
+        .unwrap_or_else(|_| vec!["schedutil".to_owned()])
+}
@@ -0,0 +1,68 @@
+use crate::settings::MinMax;
+use crate::settings::SettingError;
+
+/// Apply in-memory settings to the running system.
+///
+/// Implementors should make a best effort to apply every setting they manage,
+/// collecting (rather than aborting on) individual failures so callers can
+/// see everything that went wrong in one pass.
+pub trait OnSet {
+    fn on_set(&mut self) -> Result<(), Vec<SettingError>>;
+}
+
+/// React to the system resuming from suspend.
+pub trait OnResume {
+    fn on_resume(&self) -> Result<(), SettingError>;
+}
+
+/// A setting (or collection thereof) with hard-coded min/max bounds used for clamping.
+pub trait SettingsRange {
+    fn max() -> Self;
+    fn min() -> Self;
+}
+
+pub trait TGeneral: OnSet + OnResume {
+    fn limits(&self) -> crate::api::GeneralLimits;
+    fn json(&self) -> crate::persist::GeneralJson;
+}
+
+pub trait TGpu: OnSet + OnResume {
+    fn limits(&self) -> crate::api::GpuLimits;
+    fn json(&self) -> crate::persist::GpuJson;
+    fn provider(&self) -> crate::persist::DriverJson;
+}
+
+pub trait TBattery: OnSet + OnResume {
+    fn limits(&self) -> crate::api::BatteryLimits;
+    fn json(&self) -> crate::persist::BatteryJson;
+    fn provider(&self) -> crate::persist::DriverJson;
+}
+
+pub trait TCpus: OnSet + OnResume {
+    fn limits(&self) -> crate::api::CpusLimits;
+
+    fn json(&self) -> Vec<crate::persist::CpuJson>;
+
+    fn cpus(&mut self) -> Vec<&mut dyn TCpu>;
+
+    fn len(&self) -> usize;
+
+    fn provider(&self) -> crate::persist::DriverJson;
+
+    /// Toggle whether clock limits are applied per physical core (coalescing
+    /// paired logical cores) instead of per logical core. Drivers with no
+    /// concept of physical core pairs can ignore this.
+    fn set_clock_by_physical_core(&mut self, _value: bool) {}
+}
+
+pub trait TCpu {
+    fn online(&mut self) -> &mut bool;
+
+    fn governor(&mut self, governor: String);
+
+    fn get_governor(&self) -> &'_ str;
+
+    fn clock_limits(&mut self, limits: Option<MinMax<u64>>);
+
+    fn get_clock_limits(&self) -> Option<&MinMax<u64>>;
+}
@@ -0,0 +1,139 @@
+use crate::settings::MinMax;
+use crate::settings::{OnResume, OnSet, SettingError};
+use crate::settings::{TCpu, TCpus};
+use crate::persist::CpuJson;
+
+/// Fallback CPU driver for unrecognized hardware: reports what little it
+/// can and never writes anything.
+#[derive(Debug, Clone, Default)]
+pub struct Cpus {
+    pub cpus: Vec<Cpu>,
+}
+
+impl OnSet for Cpus {
+    fn on_set(&mut self) -> Result<(), Vec<SettingError>> {
+        Ok(())
+    }
+}
+
+impl OnResume for Cpus {
+    fn on_resume(&self) -> Result<(), SettingError> {
+        Ok(())
+    }
+}
+
+impl Cpus {
+    pub fn system_default() -> Self {
+        let count = crate::settings::steam_deck_adv::Cpus::cpu_count().unwrap_or(0);
+        Self {
+            cpus: (0..count).map(|_| Cpu::default()).collect(),
+        }
+    }
+
+    #[inline]
+    pub fn from_json(other: Vec<CpuJson>, version: u64) -> Self {
+        Self {
+            cpus: other.into_iter().map(|cpu| Cpu::from_json(cpu, version)).collect(),
+        }
+    }
+}
+
+impl TCpus for Cpus {
+    fn limits(&self) -> crate::api::CpusLimits {
+        crate::api::CpusLimits {
+            cpus: self.cpus.iter().map(|x| x.limits()).collect(),
+            count: self.cpus.len(),
+            smt_capable: false,
+            core_pairs: None,
+        }
+    }
+
+    fn json(&self) -> Vec<crate::persist::CpuJson> {
+        self.cpus.iter().map(|x| x.to_owned().into()).collect()
+    }
+
+    fn cpus(&mut self) -> Vec<&mut dyn TCpu> {
+        self.cpus.iter_mut().map(|x| x as &mut dyn TCpu).collect()
+    }
+
+    fn len(&self) -> usize {
+        self.cpus.len()
+    }
+
+    fn provider(&self) -> crate::persist::DriverJson {
+        crate::persist::DriverJson::Unknown
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Cpu {
+    pub online: bool,
+    pub clock_limits: Option<MinMax<u64>>,
+    pub governor: String,
+}
+
+impl Cpu {
+    #[inline]
+    fn from_json(other: CpuJson, version: u64) -> Self {
+        Self {
+            online: other.online,
+            clock_limits: other.clock_limits.map(|x| MinMax::from_json(x, version)),
+            governor: other.governor,
+        }
+    }
+
+    fn limits(&self) -> crate::api::CpuLimits {
+        crate::api::CpuLimits {
+            clock_min_limits: None,
+            clock_max_limits: None,
+            clock_step: 0,
+            governors: vec![],
+        }
+    }
+}
+
+impl Into<CpuJson> for Cpu {
+    #[inline]
+    fn into(self) -> CpuJson {
+        CpuJson {
+            online: self.online,
+            clock_limits: self.clock_limits.map(|x| x.into()),
+            governor: self.governor,
+            skip_resume_reclock: false,
+        }
+    }
+}
+
+impl OnSet for Cpu {
+    fn on_set(&mut self) -> Result<(), Vec<SettingError>> {
+        Ok(())
+    }
+}
+
+impl OnResume for Cpu {
+    fn on_resume(&self) -> Result<(), SettingError> {
+        Ok(())
+    }
+}
+
+impl TCpu for Cpu {
+    fn online(&mut self) -> &mut bool {
+        &mut self.online
+    }
+
+    fn governor(&mut self, governor: String) {
+        self.governor = governor;
+    }
+
+    fn get_governor(&self) -> &'_ str {
+        &self.governor
+    }
+
+    fn clock_limits(&mut self, limits: Option<MinMax<u64>>) {
+        self.clock_limits = limits;
+    }
+
+    fn get_clock_limits(&self) -> Option<&MinMax<u64>> {
+        self.clock_limits.as_ref()
+    }
+}